@@ -1,8 +1,9 @@
-use std::{ops::Range, str::Chars};
+use std::fmt;
+use std::ops::Range;
 
 /// An enum listing all tokens the lexer can encounter
 #[derive(Debug, PartialEq)]
-pub enum TokenType {
+pub enum TokenType<'a> {
     // Single-character tokens.
     Plus,
     Minus,
@@ -23,38 +24,92 @@ pub enum TokenType {
     NotEqual,
 
     // Function call like sin.
-    Function(String),
+    Function(&'a str),
 
     // Values.
     Constant(f64),
-    Variable(String),
+    Variable(&'a str),
 
     EndOfExpression,
 }
 
-pub type LexerError = String;
+/// Errors that can occur while scanning a token, each carrying the byte
+/// position (or span) where it was encountered.
+#[derive(Debug, PartialEq)]
+pub enum LexerError {
+    UnexpectedCharacter { ch: char, pos: usize },
+    MalformedNumber { span: Range<usize>, text: String },
+    UnexpectedEndOfInput { pos: usize },
+    BangWithoutEquals { found: char, pos: usize },
+    ConfusableCharacter { found: char, suggested: char, pos: usize },
+}
+
+impl fmt::Display for LexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexerError::UnexpectedCharacter { ch, pos } => {
+                write!(f, "Unexpected character at position {pos}: {ch}")
+            }
+            LexerError::MalformedNumber { span, text } => {
+                write!(f, "Bad-formatted number starting at position {}: {text}", span.start)
+            }
+            LexerError::UnexpectedEndOfInput { pos } => {
+                write!(f, "Unexpected end of input at position {pos}")
+            }
+            LexerError::BangWithoutEquals { found, pos } => {
+                write!(
+                    f,
+                    "Bang is not supposed to be combinated with something else than = at position {pos}, currently {found}"
+                )
+            }
+            LexerError::ConfusableCharacter { found, suggested, pos } => {
+                write!(
+                    f,
+                    "Confusable character '{found}' at position {pos}, did you mean '{suggested}'?"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexerError {}
+
+/// Look-alike code points mapped to the ASCII character they're commonly
+/// mistaken for, so the lexer can suggest a fix instead of just rejecting
+/// the input.
+const CONFUSABLES: &[(char, char)] = &[
+    ('∕', '/'),        // DIVISION SLASH
+    ('\u{FF0B}', '+'), // FULLWIDTH PLUS SIGN
+    ('\u{FF0D}', '-'), // FULLWIDTH HYPHEN-MINUS
+    ('\u{FF0A}', '*'), // FULLWIDTH ASTERISK
+    ('\u{FF1D}', '='), // FULLWIDTH EQUALS SIGN
+    ('\u{037E}', ';'), // GREEK QUESTION MARK
+];
+
+fn confusable_replacement(ch: char) -> Option<char> {
+    CONFUSABLES.iter().find(|(confusable, _)| *confusable == ch).map(|(_, ascii)| *ascii)
+}
 
 /// A full token representation.
-pub struct Token {
-    pub token_type: TokenType,
+pub struct Token<'a> {
+    pub token_type: TokenType<'a>,
     pub span: Range<usize>,
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, span: Range<usize>) -> Self {
+impl<'a> Token<'a> {
+    pub fn new(token_type: TokenType<'a>, span: Range<usize>) -> Self {
         Self { token_type, span }
     }
 
-    pub fn some_token(token_type: TokenType, span: Range<usize>) -> Result<Option<Self>, LexerError> {
+    pub fn some_token(token_type: TokenType<'a>, span: Range<usize>) -> Result<Option<Self>, LexerError> {
         Ok(Some(Self::new(token_type, span)))
     }
 }
 
 pub struct Lexer<'a> {
     full_str: &'a str,
-    source: Vec<char>,
     index: usize,
-    tokens_list: Vec<Token>,
+    tokens_list: Vec<Token<'a>>,
 }
 
 impl<'a> Lexer<'a> {
@@ -62,138 +117,218 @@ impl<'a> Lexer<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
             full_str: source,
-            source: source.chars().collect(),
             index: 0,
             tokens_list: Vec::new(),
         }
     }
 
-    /// Returns the next character of the source and advances by one
-    pub fn next(&mut self) -> Option<char> {
-        self.index += 1;
-
-        if self.index > self.source.len() {
-            None
-        }
-        else {
-            Some(self.source[self.index - 1])
-        }
+    /// Returns the next character of the source and advances past it
+    pub fn bump(&mut self) -> Option<char> {
+        let character = self.full_str[self.index..].chars().next()?;
+        self.index += character.len_utf8();
+        Some(character)
     }
 
     /// Returns the next character of the source without advancing the lexer
     pub fn peek(&mut self) -> Option<char> {
-        if self.index >= self.source.len() {
-            None
-        }
-        else {
-            Some(self.source[self.index])
-        }
+        self.full_str[self.index..].chars().next()
     }
 
-    pub fn add_singlechar_token(&mut self, token_type: TokenType) {
+    pub fn add_singlechar_token(&mut self, token_type: TokenType<'a>) {
         self.tokens_list
             .push(Token::new(token_type, (self.index - 1)..self.index));
     }
 
     /// Scans the next token and returns it.
-    pub fn scan_token(&mut self) -> Result<Option<Token>, String> {
+    pub fn scan_token(&mut self) -> Result<Option<Token<'a>>, LexerError> {
         let span_start = self.index + 1;
 
-        while let Some(character) = self.next() {
-            let single_char_span = self.index - 1 .. self.index;
-
-            match character {
-                // One-character tokens
-                ' ' => return Token::some_token(TokenType::Space, single_char_span),
-                '+' => return Token::some_token(TokenType::Plus, single_char_span),
-                '-' => return Token::some_token(TokenType::Minus, single_char_span),
-                '*' => return Token::some_token(TokenType::Multiply, single_char_span),
-                '/' => return Token::some_token(TokenType::Divide, single_char_span),
-                '^' => return Token::some_token(TokenType::Power, single_char_span),
-                '.' => return Token::some_token(TokenType::Dot, single_char_span),
-                '(' => return Token::some_token(TokenType::OpeningParenthesis, single_char_span),
-                ')' => return Token::some_token(TokenType::ClosingParenthesis, single_char_span),
-                '=' => return Token::some_token(TokenType::Equals, single_char_span),
-                
-                // Two-characters tokens
-                '>' => {
-                    if let Some(next) = self.peek() && next == '=' {
-                        let _ = self.next();
-                        return Token::some_token(TokenType::GreaterEqual, span_start..self.index + 1);
+        let Some(character) = self.bump() else {
+            let end = self.index.saturating_sub(1)..self.index;
+            return Ok(Some(Token::new(TokenType::EndOfExpression, end)));
+        };
+
+        let single_char_span = (self.index - character.len_utf8())..self.index;
+
+        match character {
+            // One-character tokens
+            ' ' => Token::some_token(TokenType::Space, single_char_span),
+            '+' => Token::some_token(TokenType::Plus, single_char_span),
+            '-' => Token::some_token(TokenType::Minus, single_char_span),
+            '*' => Token::some_token(TokenType::Multiply, single_char_span),
+            '/' => Token::some_token(TokenType::Divide, single_char_span),
+            '^' => Token::some_token(TokenType::Power, single_char_span),
+            '.' => Token::some_token(TokenType::Dot, single_char_span),
+            '(' => Token::some_token(TokenType::OpeningParenthesis, single_char_span),
+            ')' => Token::some_token(TokenType::ClosingParenthesis, single_char_span),
+            '=' => Token::some_token(TokenType::Equals, single_char_span),
+
+            // Unicode math operators
+            '×' | '·' => Token::some_token(TokenType::Multiply, single_char_span),
+            '÷' => Token::some_token(TokenType::Divide, single_char_span),
+            '≤' => Token::some_token(TokenType::LessEqual, single_char_span),
+            '≥' => Token::some_token(TokenType::GreaterEqual, single_char_span),
+            '≠' => Token::some_token(TokenType::NotEqual, single_char_span),
+            '−' => Token::some_token(TokenType::Minus, single_char_span),
+            '√' => Token::some_token(TokenType::Function("sqrt"), single_char_span),
+
+            // Two-characters tokens
+            '>' => {
+                if let Some(next) = self.peek() && next == '=' {
+                    let _ = self.bump();
+                    Token::some_token(TokenType::GreaterEqual, span_start..self.index + 1)
+                }
+                else {
+                    Ok(Some(Token::new(TokenType::Greater, single_char_span)))
+                }
+            },
+
+            '<' => {
+                if let Some(next) = self.peek() && next == '=' {
+                    let _ = self.bump();
+                    Token::some_token(TokenType::LessEqual, span_start..self.index + 1)
+                }
+                else {
+                    Token::some_token(TokenType::Less, single_char_span)
+                }
+            },
+
+            // `!` must be followed by `=`; anything else (including end of
+            // input) is an error rather than a silently dropped character.
+            '!' => {
+                if let Some(next) = self.peek() {
+                    if next == '=' {
+                        let _ = self.bump();
+                        Token::some_token(TokenType::NotEqual, span_start..self.index + 1)
                     }
                     else {
-                        return Ok(Some(Token::new(TokenType::Greater, single_char_span)));
+                        Err(LexerError::BangWithoutEquals { found: next, pos: self.index - 1 })
                     }
-                },
+                }
+                else {
+                    Err(LexerError::UnexpectedEndOfInput { pos: self.index })
+                }
+            },
 
-                '<' => {
-                    if let Some(next) = self.peek() && next == '=' {
-                        let _ = self.next();
-                        return Token::some_token(TokenType::LessEqual, span_start..self.index + 1);
-                    }
-                    else {
-                        return Token::some_token(TokenType::Less, single_char_span);
+            // Parsing constants
+            '0'..='9' => {
+                let span_start = self.index - 1;
+                let mut span_end = self.index;
+
+                while let Some('0'..='9' | '.' | '_') = self.peek() {
+                    self.bump();
+                    span_end += 1;
+                }
+
+                // Scientific notation: `e`/`E`, an optional sign, then at least one digit.
+                if let Some(marker) = self.peek() && (marker == 'e' || marker == 'E') {
+                    let _ = self.bump();
+                    span_end += 1;
+
+                    if let Some(sign) = self.peek() && (sign == '+' || sign == '-') {
+                        let _ = self.bump();
+                        span_end += 1;
                     }
-                },
 
-                '!' => {
-                    if let Some(next) = self.peek() {
-                        if next == '=' {
-                            let _ = self.next();
-                            return Token::some_token(TokenType::NotEqual, span_start..self.index + 1);
-                        }
-                        else {
-                            return Err(format!("Bang is not supposed to be combinated with something else than = at position {}, currently {}", self.index - 1, next));
-                        }
-                        
+                    let mut has_exponent_digits = false;
+                    while let Some('0'..='9') = self.peek() {
+                        let _ = self.bump();
+                        span_end += 1;
+                        has_exponent_digits = true;
                     }
-                },
-
-                // Parsing constants
-                '0'..='9' => {
-                    let span_start = self.index - 1;
-                    let mut span_end = self.index;
-                    loop {
-                        match self.peek() {
-                            Some('0'..='9') | Some('.') | Some('_') => span_end += 1,
-                            _ => {
-                                let parsed_value = self.full_str[span_start..span_end].parse::<f64>();
-                                if parsed_value.is_err() {
-                                    let string = self.full_str[span_start..span_end].to_owned();
-                                    return Err(format!("Bad-formatted number starting at position {}: {}", span_start, string));
-                                }
-                                return Token::some_token(TokenType::Constant(parsed_value.unwrap()), span_start..span_end);
-                            }
-                        }
-                        let _ = self.next();
+
+                    if !has_exponent_digits {
+                        let text = self.full_str[span_start..span_end].to_owned();
+                        return Err(LexerError::MalformedNumber { span: span_start..span_end, text });
                     }
                 }
-                // End parsing constants
-
-                // Parsing variables
-                'a'..='z' | 'A'..='Z' => {
-                    let span_start = self.index - 1;
-                    let mut span_end = self.index;
-                    loop {
-                        match self.peek() {
-                            Some('a'..='z') | Some('A'..='Z') => span_end += 1,
-                            _ => {
-                                let variable_name = self.full_str[span_start..span_end].to_owned();
-                                return Token::some_token(TokenType::Variable(variable_name), span_start..span_end);
-                            }
+
+                let parsed_value = self.full_str[span_start..span_end].parse::<f64>();
+                if parsed_value.is_err() {
+                    let text = self.full_str[span_start..span_end].to_owned();
+                    return Err(LexerError::MalformedNumber { span: span_start..span_end, text });
+                }
+                Token::some_token(TokenType::Constant(parsed_value.unwrap()), span_start..span_end)
+            }
+            // End parsing constants
+
+            // Parsing variables and function calls: an identifier directly
+            // followed by `(`, with no space in between, is a function call.
+            'a'..='z' | 'A'..='Z' => {
+                let span_start = self.index - 1;
+                let mut span_end = self.index;
+                loop {
+                    match self.peek() {
+                        Some('a'..='z') | Some('A'..='Z') => span_end += 1,
+                        _ => {
+                            let name = &self.full_str[span_start..span_end];
+                            let token_type = if self.peek() == Some('(') {
+                                TokenType::Function(name)
+                            } else {
+                                TokenType::Variable(name)
+                            };
+                            return Token::some_token(token_type, span_start..span_end);
                         }
-                        self.next();
                     }
+                    self.bump();
+                }
+            }
+            _ => {
+                let pos = self.index - character.len_utf8();
+                if let Some(suggested) = confusable_replacement(character) {
+                    Err(LexerError::ConfusableCharacter { found: character, suggested, pos })
                 }
-                _ => {
-                    return Err(format!(
-                        "Unexpected character at position {}: {}",
-                        self.index - 1,
-                        character
-                    ))
+                else {
+                    Err(LexerError::UnexpectedCharacter { ch: character, pos })
                 }
             }
         }
-        Ok(Some(Token::new(TokenType::EndOfExpression, self.index - 1 .. self.index)))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexerError>;
+
+    /// Yields tokens lazily, stopping after `EndOfExpression` (exclusive).
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.scan_token() {
+            Ok(Some(token)) if token.token_type == TokenType::EndOfExpression => None,
+            Ok(Some(token)) => Some(Ok(token)),
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// Lexes `input` in full, stopping after `EndOfExpression`.
+pub fn lex(input: &str) -> Result<Vec<Token<'_>>, LexerError> {
+    let mut lexer = Lexer::new(input);
+    let mut tokens = Vec::new();
+
+    loop {
+        match lexer.scan_token()? {
+            Some(token) if token.token_type == TokenType::EndOfExpression => break,
+            Some(token) => tokens.push(token),
+            None => break,
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lex_empty_input_returns_no_tokens() {
+        assert!(lex("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn lex_whitespace_only_input_returns_space_tokens() {
+        let tokens = lex("  ").unwrap();
+        assert!(tokens.iter().all(|token| token.token_type == TokenType::Space));
+        assert_eq!(tokens.len(), 2);
     }
 }
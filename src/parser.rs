@@ -0,0 +1,403 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::lexer::{Lexer, LexerError, Token, TokenType};
+
+/// A comparison operator appearing inside a chained [`Expr::Compare`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+/// An arithmetic operator applied to two operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+/// A sign applied to a single operand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnOp {
+    Plus,
+    Minus,
+}
+
+/// A node of the expression tree built by the [`Parser`].
+#[derive(Debug, PartialEq)]
+pub enum Expr<'a> {
+    Number(f64),
+    Variable(&'a str),
+    Unary {
+        op: UnOp,
+        rhs: Box<Expr<'a>>,
+    },
+    Binary {
+        op: BinOp,
+        lhs: Box<Expr<'a>>,
+        rhs: Box<Expr<'a>>,
+    },
+    Call {
+        name: &'a str,
+        args: Vec<Expr<'a>>,
+    },
+    // A run of chained comparisons (`5 <= x <= 35`), evaluated as a conjunction.
+    // `chain[0]`'s operator is never read: it only carries the first operand.
+    Compare {
+        chain: Vec<(CmpOp, Expr<'a>)>,
+    },
+}
+
+/// Errors that can occur while parsing a token stream into an [`Expr`].
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Lexer(LexerError),
+    UnexpectedToken { found: String, pos: usize },
+    UnexpectedEndOfInput,
+}
+
+/// Errors that can occur while evaluating an [`Expr`].
+#[derive(Debug, PartialEq)]
+pub enum EvalError {
+    UnknownVariable(String),
+    UnknownFunction(String),
+    WrongArgumentCount { name: String, expected: usize, found: usize },
+    DivisionByZero,
+}
+
+// Binding powers, lowest to highest. Each pair is (left, right); a right bp
+// one less than its left bp makes the operator right-associative.
+const COMPARE_LBP: u8 = 1;
+const COMPARE_RBP: u8 = 3;
+const ADD_LBP: u8 = 3;
+const ADD_RBP: u8 = 4;
+const MUL_LBP: u8 = 5;
+const MUL_RBP: u8 = 6;
+const POWER_LBP: u8 = 8;
+const POWER_RBP: u8 = 7;
+const UNARY_BP: u8 = MUL_RBP;
+
+/// Consumes a [`Lexer`]'s token stream and builds an [`Expr`] using
+/// recursive-descent/Pratt parsing.
+pub struct Parser<'a> {
+    tokens: VecDeque<Token<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    /// Lexes `source` in full and prepares a parser over the resulting tokens.
+    pub fn new(source: &'a str) -> Result<Self, ParseError> {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = VecDeque::new();
+
+        loop {
+            match lexer.scan_token() {
+                Ok(Some(token)) => {
+                    let is_end = token.token_type == TokenType::EndOfExpression;
+                    if token.token_type != TokenType::Space {
+                        tokens.push_back(token);
+                    }
+                    if is_end {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(error) => return Err(ParseError::Lexer(error)),
+            }
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// Parses the whole token stream into a single expression. Errors if
+    /// tokens remain after the expression (e.g. a stray closing paren).
+    pub fn parse(&mut self) -> Result<Expr<'a>, ParseError> {
+        let expr = self.parse_expr(0)?;
+
+        match self.peek() {
+            None | Some(TokenType::EndOfExpression) => Ok(expr),
+            Some(_) => {
+                let token = self.advance().expect("peek returned Some");
+                Err(ParseError::UnexpectedToken {
+                    found: format!("{:?}", token.token_type),
+                    pos: token.span.start,
+                })
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<&TokenType<'a>> {
+        self.tokens.front().map(|token| &token.token_type)
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        self.tokens.pop_front()
+    }
+
+    fn expect(&mut self, expected: TokenType<'a>) -> Result<(), ParseError> {
+        match self.advance() {
+            Some(token) if token.token_type == expected => Ok(()),
+            Some(token) => Err(ParseError::UnexpectedToken {
+                found: format!("{:?}", token.token_type),
+                pos: token.span.start,
+            }),
+            None => Err(ParseError::UnexpectedEndOfInput),
+        }
+    }
+
+    fn peek_cmp_op(&self) -> Option<CmpOp> {
+        match self.peek()? {
+            TokenType::Equals => Some(CmpOp::Equal),
+            TokenType::NotEqual => Some(CmpOp::NotEqual),
+            TokenType::Less => Some(CmpOp::Less),
+            TokenType::LessEqual => Some(CmpOp::LessEqual),
+            TokenType::Greater => Some(CmpOp::Greater),
+            TokenType::GreaterEqual => Some(CmpOp::GreaterEqual),
+            _ => None,
+        }
+    }
+
+    fn peek_bin_op(&self) -> Option<(BinOp, u8, u8)> {
+        match self.peek()? {
+            TokenType::Plus => Some((BinOp::Add, ADD_LBP, ADD_RBP)),
+            TokenType::Minus => Some((BinOp::Subtract, ADD_LBP, ADD_RBP)),
+            TokenType::Multiply => Some((BinOp::Multiply, MUL_LBP, MUL_RBP)),
+            TokenType::Divide => Some((BinOp::Divide, MUL_LBP, MUL_RBP)),
+            TokenType::Power => Some((BinOp::Power, POWER_LBP, POWER_RBP)),
+            _ => None,
+        }
+    }
+
+    /// Whether the next token can start a prefix, i.e. a value directly
+    /// follows another value with no operator in between (`2x`, `3(x+1)`).
+    fn starts_implicit_multiply(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(TokenType::Constant(_))
+                | Some(TokenType::Variable(_))
+                | Some(TokenType::Function(_))
+                | Some(TokenType::OpeningParenthesis)
+        )
+    }
+
+    fn parse_expr(&mut self, min_bp: u8) -> Result<Expr<'a>, ParseError> {
+        let mut lhs = self.parse_prefix()?;
+
+        loop {
+            if let Some(first_cmp) = self.peek_cmp_op() {
+                if COMPARE_LBP < min_bp {
+                    break;
+                }
+                lhs = self.parse_compare_chain(lhs, first_cmp)?;
+                break;
+            }
+
+            if let Some((op, l_bp, r_bp)) = self.peek_bin_op() {
+                if l_bp < min_bp {
+                    break;
+                }
+                self.advance();
+                let rhs = self.parse_expr(r_bp)?;
+                lhs = Expr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+                continue;
+            }
+
+            if self.starts_implicit_multiply() {
+                if MUL_LBP < min_bp {
+                    break;
+                }
+                let rhs = self.parse_expr(MUL_RBP)?;
+                lhs = Expr::Binary { op: BinOp::Multiply, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+                continue;
+            }
+
+            break;
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_compare_chain(&mut self, first: Expr<'a>, first_cmp: CmpOp) -> Result<Expr<'a>, ParseError> {
+        let mut chain = vec![(first_cmp, first)];
+
+        while let Some(cmp) = self.peek_cmp_op() {
+            self.advance();
+            let operand = self.parse_expr(COMPARE_RBP)?;
+            chain.push((cmp, operand));
+        }
+
+        Ok(Expr::Compare { chain })
+    }
+
+    fn parse_prefix(&mut self) -> Result<Expr<'a>, ParseError> {
+        let token = self.advance().ok_or(ParseError::UnexpectedEndOfInput)?;
+        let pos = token.span.start;
+
+        match token.token_type {
+            TokenType::Constant(value) => Ok(Expr::Number(value)),
+            TokenType::Variable(name) => Ok(Expr::Variable(name)),
+
+            TokenType::Function(name) => {
+                self.expect(TokenType::OpeningParenthesis)?;
+                let arg = self.parse_expr(0)?;
+                self.expect(TokenType::ClosingParenthesis)?;
+                Ok(Expr::Call { name, args: vec![arg] })
+            }
+
+            TokenType::OpeningParenthesis => {
+                let inner = self.parse_expr(0)?;
+                self.expect(TokenType::ClosingParenthesis)?;
+                Ok(inner)
+            }
+
+            TokenType::Minus => {
+                let rhs = self.parse_expr(UNARY_BP)?;
+                Ok(Expr::Unary { op: UnOp::Minus, rhs: Box::new(rhs) })
+            }
+
+            TokenType::Plus => {
+                let rhs = self.parse_expr(UNARY_BP)?;
+                Ok(Expr::Unary { op: UnOp::Plus, rhs: Box::new(rhs) })
+            }
+
+            other => Err(ParseError::UnexpectedToken { found: format!("{other:?}"), pos }),
+        }
+    }
+}
+
+/// Evaluates `expr`, resolving variables from `vars`.
+pub fn eval(expr: &Expr<'_>, vars: &HashMap<String, f64>) -> Result<f64, EvalError> {
+    match expr {
+        Expr::Number(value) => Ok(*value),
+
+        Expr::Variable(name) => vars
+            .get(*name)
+            .copied()
+            .ok_or_else(|| EvalError::UnknownVariable((*name).to_owned())),
+
+        Expr::Unary { op, rhs } => {
+            let value = eval(rhs, vars)?;
+            Ok(match op {
+                UnOp::Plus => value,
+                UnOp::Minus => -value,
+            })
+        }
+
+        Expr::Binary { op, lhs, rhs } => {
+            let lhs = eval(lhs, vars)?;
+            let rhs = eval(rhs, vars)?;
+            match op {
+                BinOp::Add => Ok(lhs + rhs),
+                BinOp::Subtract => Ok(lhs - rhs),
+                BinOp::Multiply => Ok(lhs * rhs),
+                BinOp::Divide => {
+                    if rhs == 0.0 {
+                        Err(EvalError::DivisionByZero)
+                    } else {
+                        Ok(lhs / rhs)
+                    }
+                }
+                BinOp::Power => Ok(lhs.powf(rhs)),
+            }
+        }
+
+        Expr::Call { name, args } => {
+            if args.len() != 1 {
+                return Err(EvalError::WrongArgumentCount {
+                    name: (*name).to_owned(),
+                    expected: 1,
+                    found: args.len(),
+                });
+            }
+            let arg = eval(&args[0], vars)?;
+            match *name {
+                "sin" => Ok(arg.sin()),
+                "cos" => Ok(arg.cos()),
+                "tan" => Ok(arg.tan()),
+                "sqrt" => Ok(arg.sqrt()),
+                "abs" => Ok(arg.abs()),
+                "ln" => Ok(arg.ln()),
+                "log" => Ok(arg.log10()),
+                "exp" => Ok(arg.exp()),
+                _ => Err(EvalError::UnknownFunction((*name).to_owned())),
+            }
+        }
+
+        Expr::Compare { chain } => {
+            let mut prev = eval(&chain[0].1, vars)?;
+            let mut holds = true;
+
+            for (op, expr) in &chain[1..] {
+                let current = eval(expr, vars)?;
+                holds &= match op {
+                    CmpOp::Equal => prev == current,
+                    CmpOp::NotEqual => prev != current,
+                    CmpOp::Less => prev < current,
+                    CmpOp::LessEqual => prev <= current,
+                    CmpOp::Greater => prev > current,
+                    CmpOp::GreaterEqual => prev >= current,
+                };
+                prev = current;
+            }
+
+            Ok(if holds { 1.0 } else { 0.0 })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_str(source: &str, vars: &HashMap<String, f64>) -> Result<f64, EvalError> {
+        let expr = Parser::new(source).unwrap().parse().unwrap();
+        eval(&expr, vars)
+    }
+
+    #[test]
+    fn power_is_right_associative() {
+        let vars = HashMap::new();
+        assert_eq!(eval_str("2^3^2", &vars), Ok(512.0));
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        let mut vars = HashMap::new();
+        vars.insert("x".to_owned(), 3.0);
+        assert_eq!(eval_str("2x", &vars), Ok(6.0));
+    }
+
+    #[test]
+    fn comparison_chain_is_a_conjunction() {
+        let mut vars = HashMap::new();
+        vars.insert("test".to_owned(), 1.0);
+        assert_eq!(eval_str("5 <= 35*test^2 <= 35", &vars), Ok(1.0));
+
+        vars.insert("test".to_owned(), 2.0);
+        assert_eq!(eval_str("5 <= 35*test^2 <= 35", &vars), Ok(0.0));
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        let vars = HashMap::new();
+        assert_eq!(eval_str("1/0", &vars), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn trailing_tokens_are_rejected() {
+        let err = Parser::new("1)+2").unwrap().parse().unwrap_err();
+        assert!(matches!(err, ParseError::UnexpectedToken { .. }));
+    }
+
+    #[test]
+    fn named_function_calls_are_recognized() {
+        let vars = HashMap::new();
+        assert_eq!(eval_str("sqrt(4)", &vars), Ok(2.0));
+    }
+}